@@ -0,0 +1,265 @@
+/* ====================================================================================================
+  LOCK-FREE MULTI-PRODUCER/MULTI-CONSUMER QUEUE (Michael-Scott Algorithm)
+====================================================================================================
+
+OVERVIEW:
+  An alternative to queue.rs's Mutex<State<T>>-based SharedQueue for the high-throughput
+  producer-consumer path. Instead of one lock guarding the whole list, this is a classic
+  Michael-Scott linked-list queue: producers and consumers race on compare-and-swap (CAS)
+  operations against `head`/`tail` pointers instead of blocking on a mutex.
+
+PROGRAM FLOW:
+  Same shape as queue.rs's demo: 4 producers enqueue a fixed share of 1M integers, 4 consumers
+  drain until shutdown, then the program reports how many items each consumer processed.
+
+KEY STRUCTURES:
+  • Node<T>: A heap-allocated link cell holding `Option<T>` and an `AtomicPtr` to the next cell
+  • LockFreeQueue<T>: head/tail `AtomicPtr<Node<T>>` plus a park/unpark registry for consumers
+
+CORE FUNCTIONS:
+  • new(): Allocates a single dummy node and points both head and tail at it
+  • enqueue(item): CAS-links a new node onto the tail, helping a lagging tail along the way
+  • dequeue(): CAS-advances head past the dummy/consumed node; parks when the queue looks empty
+  • send_shutdown(): Sets the shutdown flag and unparks every registered consumer
+
+WHY A DUMMY NODE:
+  head and tail always point at a real node, even when the queue is logically empty. That keeps
+  every CAS a same-type pointer swap instead of needing a special "null" case for the empty
+  queue, which is the standard trick in the Michael-Scott paper.
+
+BLOCKING WITHOUT A MUTEX:
+  A purely lock-free queue can't `Condvar::wait` (that requires a mutex). Instead, a consumer
+  that observes an empty queue registers its `Thread` handle, keyed by `ThreadId` so re-parking
+  on a still-empty queue overwrites its own prior entry instead of piling up a duplicate, and
+  calls `thread::park_timeout`; `enqueue` calls `unpark` on one registered waiter after linking
+  its node, and `send_shutdown` unparks every registered waiter. The timeout (mirroring the
+  bounded poll used for idle workers in tcp_server.rs) is a safety net against a producer's
+  `unpark` (or `send_shutdown`) landing after a consumer's emptiness check but before that
+  consumer has registered — worst case it costs one extra poll, not a stuck thread.
+
+ABA HAZARD:
+  This implementation does not tag pointers with a version counter. A classic ABA sequence —
+  thread A reads `head`, gets preempted, the node at `head` is dequeued and its address is
+  reused for a brand new allocation that lands back at the same address, then A's CAS succeeds
+  against a node it never actually observed — is possible in principle. It does not bite here
+  only because dequeued nodes are never freed (see below), so an address can never be reused
+  while the queue is alive.
+
+MEMORY RECLAMATION:
+  `dequeue` never frees the node it advances `head` past — doing so without hazard pointers or
+  an epoch scheme is unsound here: another thread may have already loaded that same `head`
+  pointer and would dereference freed memory. So this queue leaks one node per successfully
+  dequeued item for as long as it's multi-consumer-safe. Reclaiming that memory would only be
+  sound if a caller could guarantee a single consumer (nothing else can be racing the node you
+  just advanced past) or if this were rebuilt on top of an epoch-based reclamation scheme
+  (crossbeam-epoch being the usual off-the-shelf choice). Neither is implemented here.
+
+==================================================================================================== */
+
+use std::collections::HashMap;
+use std::ptr;
+use std::sync::atomic::{AtomicBool, AtomicPtr, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, Thread, ThreadId};
+use std::time::{Duration, Instant};
+
+// Bounded poll interval for a parked consumer, mirroring tcp_server.rs's POLL_INTERVAL: bounds
+// wake-up latency without requiring every enqueue to prove it unparked the right sleeper.
+const PARK_TIMEOUT: Duration = Duration::from_millis(10);
+
+struct Node<T> {
+    data: Option<T>,
+    next: AtomicPtr<Node<T>>,
+}
+
+impl<T> Node<T> {
+    fn new(data: Option<T>) -> *mut Node<T> {
+        Box::into_raw(Box::new(Node {
+            data,
+            next: AtomicPtr::new(ptr::null_mut()),
+        }))
+    }
+}
+
+pub struct LockFreeQueue<T> {
+    head: AtomicPtr<Node<T>>,
+    tail: AtomicPtr<Node<T>>,
+    shutdown: AtomicBool,
+    // Keyed by ThreadId so a consumer re-registering on every empty re-check overwrites its own
+    // prior entry instead of piling up a fresh one, which would otherwise grow this map without
+    // bound on a quiet queue.
+    parked: Mutex<HashMap<ThreadId, Thread>>,
+}
+
+unsafe impl<T: Send> Send for LockFreeQueue<T> {}
+unsafe impl<T: Send> Sync for LockFreeQueue<T> {}
+
+impl<T> LockFreeQueue<T> {
+    pub fn new() -> Self {
+        let dummy = Node::new(None);
+        LockFreeQueue {
+            head: AtomicPtr::new(dummy),
+            tail: AtomicPtr::new(dummy),
+            shutdown: AtomicBool::new(false),
+            parked: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn enqueue(&self, item: T) {
+        let new_node = Node::new(Some(item));
+
+        loop {
+            let tail = self.tail.load(Ordering::Acquire);
+            // SAFETY: `tail` always points at a live node — either the dummy allocated in `new`
+            // or a node linked by a prior `enqueue` — and nodes are never freed (see module docs).
+            let tail_ref = unsafe { &*tail };
+            let next = tail_ref.next.load(Ordering::Acquire);
+
+            if next.is_null() {
+                if tail_ref
+                    .next
+                    .compare_exchange(ptr::null_mut(), new_node, Ordering::Release, Ordering::Relaxed)
+                    .is_ok()
+                {
+                    // Best-effort: swing tail to the node we just linked. If this CAS loses, a
+                    // helper (below) or our own next call will advance it instead.
+                    let _ = self
+                        .tail
+                        .compare_exchange(tail, new_node, Ordering::Release, Ordering::Relaxed);
+                    break;
+                }
+            } else {
+                // Tail is lagging behind the real end of the list; help it catch up before retrying.
+                let _ = self
+                    .tail
+                    .compare_exchange(tail, next, Ordering::Release, Ordering::Relaxed);
+            }
+        }
+
+        self.wake_one();
+    }
+
+    pub fn dequeue(&self) -> Option<T> {
+        loop {
+            let head = self.head.load(Ordering::Acquire);
+            let tail = self.tail.load(Ordering::Acquire);
+            // SAFETY: same invariant as in `enqueue` — live nodes are never freed.
+            let head_ref = unsafe { &*head };
+            let next = head_ref.next.load(Ordering::Acquire);
+
+            if head == tail {
+                if next.is_null() {
+                    if self.shutdown.load(Ordering::Acquire) {
+                        return None;
+                    }
+
+                    let current = thread::current();
+                    self.parked.lock().unwrap().insert(current.id(), current);
+                    thread::park_timeout(PARK_TIMEOUT);
+                    continue;
+                }
+
+                // Tail is lagging behind an item that's already linked in; help it along.
+                let _ = self
+                    .tail
+                    .compare_exchange(tail, next, Ordering::Release, Ordering::Relaxed);
+                continue;
+            }
+
+            if next.is_null() {
+                // head != tail implies a next node must exist; if we raced a concurrent helper,
+                // just retry the read.
+                continue;
+            }
+
+            if self
+                .head
+                .compare_exchange(head, next, Ordering::Release, Ordering::Relaxed)
+                .is_ok()
+            {
+                // We own `next`'s data now: `next` becomes the new `head`, and only the one
+                // dequeuer whose CAS advanced `head` onto it ever reads its data (later dequeuers
+                // only follow its `next` link), so taking the data is safe even though the node
+                // itself is intentionally leaked (see module docs on memory reclamation).
+                let value = unsafe { (*next).data.take() };
+                return value;
+            }
+        }
+    }
+
+    pub fn send_shutdown(&self) {
+        self.shutdown.store(true, Ordering::Release);
+        let waiters = std::mem::take(&mut *self.parked.lock().unwrap());
+        for (_, waiter) in waiters {
+            waiter.unpark();
+        }
+    }
+
+    fn wake_one(&self) {
+        let mut parked = self.parked.lock().unwrap();
+        if let Some(&id) = parked.keys().next() {
+            let waiter = parked.remove(&id).unwrap();
+            drop(parked);
+            waiter.unpark();
+        }
+    }
+}
+
+impl<T> Default for LockFreeQueue<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn main() {
+    const TOTAL_ITEMS: usize = 1_000_000;
+    const NUM_PRODUCERS: usize = 4;
+    const NUM_CONSUMERS: usize = 4;
+
+    let items_per_producer = TOTAL_ITEMS / NUM_PRODUCERS;
+    let shared_queue = Arc::new(LockFreeQueue::<i32>::new());
+    let start_time = Instant::now();
+    let mut consumer_handles = vec![];
+    let mut producer_handles = vec![];
+
+    println!("--- Starting Lock-Free Queue Simulation ---");
+    println!("Producers: {}, Consumers: {}, Total Items: {}", NUM_PRODUCERS, NUM_CONSUMERS, TOTAL_ITEMS);
+
+    for id in 0..NUM_CONSUMERS {
+        let q = Arc::clone(&shared_queue);
+        let handle = thread::spawn(move || {
+            let mut count = 0;
+            while let Some(_) = q.dequeue() {
+                count += 1;
+            }
+            println!("Consumer {} finished. Processed {} items.", id, count);
+        });
+        consumer_handles.push(handle);
+    }
+
+    for id in 0..NUM_PRODUCERS {
+        let q = Arc::clone(&shared_queue);
+        let handle = thread::spawn(move || {
+            for j in 0..items_per_producer {
+                let val = (id * items_per_producer + j) as i32;
+                q.enqueue(val);
+            }
+        });
+        producer_handles.push(handle);
+    }
+
+    for h in producer_handles {
+        h.join().unwrap();
+    }
+    println!("All Producers finished writing.");
+
+    shared_queue.send_shutdown();
+
+    for h in consumer_handles {
+        h.join().unwrap();
+    }
+
+    let duration = start_time.elapsed();
+    println!("--- All operations complete ---");
+    println!("Time taken: {:.2?}", duration);
+}
@@ -3,6 +3,12 @@ use std::sync::Arc;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::time::Instant;
 
+// Size of the contiguous range a thread claims per fetch_add. `fetch_add(1, ..)` on a single
+// shared counter means every candidate number is a contended atomic RMW on one cache line; by
+// claiming CHUNK_SIZE numbers at a time, that cost is amortized over tens of thousands of local,
+// uncontended primality tests instead of paid once per number.
+const CHUNK_SIZE: usize = 65_536;
+
 fn is_prime(n: usize) -> bool {
     if n <= 1 { return false; }
     if n <= 3 { return true; }
@@ -15,15 +21,52 @@ fn is_prime(n: usize) -> bool {
     true
 }
 
+// Pins the calling thread to `core_id` so siblings sharing an L3 cache shard (typically
+// consecutive core ids on one socket) stay close together, cutting cross-socket coherence
+// traffic. Only implemented for Linux, where `sched_setaffinity` is a stable syscall; other
+// platforms log a warning and run unpinned rather than silently doing nothing different from
+// what was asked.
+#[cfg(target_os = "linux")]
+fn pin_to_core(core_id: usize) {
+    use std::mem;
+
+    // cpu_set_t is a CPU_SETSIZE (1024) bit mask; 16 u64 words cover that.
+    const MASK_WORDS: usize = 16;
+    let mut mask = [0u64; MASK_WORDS];
+    let word = core_id / 64;
+    let bit = core_id % 64;
+    if word < MASK_WORDS {
+        mask[word] = 1u64 << bit;
+    }
+
+    extern "C" {
+        fn sched_setaffinity(pid: i32, cpusetsize: usize, mask: *const u64) -> i32;
+    }
+
+    // SAFETY: `mask` is a correctly sized, stack-local cpu_set_t bitmap and `pid == 0` targets
+    // the calling thread, matching sched_setaffinity's documented contract.
+    let result = unsafe { sched_setaffinity(0, mem::size_of_val(&mask), mask.as_ptr()) };
+
+    if result != 0 {
+        eprintln!("Warning: failed to pin thread to core {core_id} (sched_setaffinity returned {result})");
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn pin_to_core(core_id: usize) {
+    eprintln!("Warning: thread pinning is not supported on this platform; thread for core {core_id} runs unpinned.");
+}
+
 fn main() {
     let limit = 100_000_000;
     let num_threads = 10;
-    
+    let pin_threads = true;
+
     // Arc (Atomic Reference Counter) allows multiple threads to own the same data.
     // AtomicUsize allows threads to safely update a shared counter without "locking."
     let counter = Arc::new(AtomicUsize::new(2)); // Start at 2
     let total_primes = Arc::new(AtomicUsize::new(0));
-    
+
     let mut handles = vec![];
     let start_total = Instant::now();
 
@@ -32,23 +75,33 @@ fn main() {
         let total_ref = Arc::clone(&total_primes);
 
         let handle = thread::spawn(move || {
+            if pin_threads {
+                pin_to_core(t);
+            }
+
             let thread_start = Instant::now();
             let mut local_count = 0;
 
             loop {
-                // Fetch the next number and increment the global counter atomically
-                let num = counter_ref.fetch_add(1, Ordering::SeqCst);
-                
-                if num > limit { break; } 
+                // Claim a contiguous block of the range instead of one number at a time.
+                let block_start = counter_ref.fetch_add(CHUNK_SIZE, Ordering::SeqCst);
 
-                if is_prime(num) {
-                    local_count += 1;
+                if block_start > limit {
+                    break;
+                }
+
+                let block_end = (block_start + CHUNK_SIZE).min(limit + 1);
+
+                for num in block_start..block_end {
+                    if is_prime(num) {
+                        local_count += 1;
+                    }
                 }
             }
 
-            // Add this thread's findings to the global total
+            // Add this thread's findings to the global total, once per thread.
             total_ref.fetch_add(local_count, Ordering::Relaxed);
-            
+
             println!("Thread {:2}: Finished in {:?}.", t, thread_start.elapsed());
         });
 
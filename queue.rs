@@ -15,43 +15,70 @@ PROGRAM FLOW:
   5. Send shutdown signal to notify consumers no more data is coming
   6. Wait for consumers to drain remaining items and exit
   7. Display final statistics (time taken, items processed)
+  8. Run the Selector fan-in demo: two producer pools feed separate queues, one consumer drains
+     both via select() until both are shut down and drained
 
 KEY STRUCTURES:
-  • State<T>: Internal structure holding the VecDeque and shutdown flag
-  • SharedQueue<T>: Thread-safe wrapper using Mutex and Condvar
+  • State<T>: Internal structure holding the VecDeque, shutdown flag and optional capacity
+  • SharedQueue<T>: Thread-safe wrapper using Mutex and a pair of Condvars
 
 CORE FUNCTIONS:
-  • new(): Creates an empty queue with shutdown=false
-  • enqueue(item): Adds item to queue back, notifies one waiting consumer
+  • new(): Creates an unbounded queue with shutdown=false
+  • with_capacity(n): Creates a queue that blocks producers once `n` items are queued
+  • enqueue(item): Blocks while the queue is at capacity, then pushes and notifies a consumer
   • dequeue(): Removes item from queue front; blocks if empty until data arrives or shutdown
-  • send_shutdown(): Sets shutdown flag and wakes all sleeping consumers
+  • send_shutdown(): Sets shutdown flag and wakes all sleeping consumers and producers
   • size(): Returns current queue length
 
 CONCURRENCY MECHANISMS:
   • Mutex<State<T>>: Ensures exclusive access to queue and shutdown flag
-  • Condvar: Allows threads to sleep efficiently when waiting for data
+  • Condvar (not_empty): Allows consumers to sleep efficiently when waiting for data
+  • Condvar (not_full): Allows producers to sleep efficiently when the queue is at capacity
   • Arc<SharedQueue<T>>: Enables safe shared ownership across threads
 
+BACKPRESSURE:
+  An unbounded queue lets a fast producer outrun slow consumers and balloon memory, so
+  `with_capacity(n)` caps the queue at `n` items (n >= 1). Once full, `enqueue` parks on
+  `not_full` until a consumer frees a slot or shutdown is signaled, mirroring std's bounded
+  `mpsc::sync_channel(n)` for n >= 1 — unlike `sync_channel`, capacity 0's rendezvous hand-off
+  isn't supported here (see `with_capacity`), since this queue's producer and consumer sides
+  only ever meet through the shared `Mutex<State<T>>`, never directly.
+
 GRACEFUL SHUTDOWN:
   Producers finish → send_shutdown() called → Consumers drain queue → All threads exit cleanly.
   The dequeue() function returns None when both the queue is empty AND shutdown is signaled,
   allowing consumers to distinguish between "temporarily empty" and "permanently done".
 
+SELECTING ACROSS QUEUES:
+  dequeue() only ever blocks on one queue. Selector registers a shared wakeup token (an
+  AtomicUsize ready-count plus a Condvar) with several SharedQueues; their enqueue/send_shutdown
+  notify that token in addition to their own not_empty condvar. select() parks on the shared
+  token, then polls each registered queue's non-blocking pop in registration order to pick a
+  winner — letting one consumer fan in from multiple producer pools.
+
 ==================================================================================================== */
 
 use std::collections::VecDeque;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Condvar, Mutex};
 use std::thread;
-use std::time::Instant;
+use std::time::{Duration, Instant};
+
+// Bounded poll interval for Selector::select (the not_full/not_empty waits in this file have no
+// timeout): a lost wakeup costs one extra poll, not a stuck consumer.
+const SELECT_POLL_INTERVAL: Duration = Duration::from_millis(10);
 
 struct State<T> {
     queue: VecDeque<T>,
     shutdown: bool,
+    capacity: Option<usize>,
 }
 
 struct SharedQueue<T> {
     state: Mutex<State<T>>,
-    condvar: Condvar,
+    not_empty: Condvar,
+    not_full: Condvar,
+    wakeup: Mutex<Option<Arc<WakeToken>>>,
 }
 
 impl<T> SharedQueue<T> {
@@ -60,20 +87,52 @@ impl<T> SharedQueue<T> {
             state: Mutex::new(State {
                 queue: VecDeque::new(),
                 shutdown: false,
+                capacity: None,
             }),
-            condvar: Condvar::new(),
+            not_empty: Condvar::new(),
+            not_full: Condvar::new(),
+            wakeup: Mutex::new(None),
+        }
+    }
+
+    // capacity 0 is forbidden rather than given rendezvous semantics: `enqueue`/`dequeue` here
+    // are two separate hand-offs through a Mutex<State<T>>, not the single synchronized exchange
+    // that `mpsc::sync_channel(0)` implements, so an item parked by `enqueue` waiting for
+    // capacity to free up can never actually observe a waiting consumer and would deadlock forever.
+    fn with_capacity(capacity: usize) -> Self {
+        assert!(capacity > 0, "SharedQueue capacity must be at least 1; 0 would deadlock every enqueue");
+        SharedQueue {
+            state: Mutex::new(State {
+                queue: VecDeque::new(),
+                shutdown: false,
+                capacity: Some(capacity),
+            }),
+            not_empty: Condvar::new(),
+            not_full: Condvar::new(),
+            wakeup: Mutex::new(None),
         }
     }
 
     fn enqueue(&self, item: T) {
         let mut state = self.state.lock().unwrap();
-        
-        if state.shutdown {
-            panic!("Cannot enqueue items after shutdown signal!");
+
+        loop {
+            if state.shutdown {
+                panic!("Cannot enqueue items after shutdown signal!");
+            }
+
+            match state.capacity {
+                Some(capacity) if state.queue.len() >= capacity => {
+                    state = self.not_full.wait(state).unwrap();
+                }
+                _ => break,
+            }
         }
 
         state.queue.push_back(item);
-        self.condvar.notify_one();
+        self.not_empty.notify_one();
+        drop(state);
+        self.notify_wakeup();
     }
 
     fn dequeue(&self) -> Option<T> {
@@ -81,6 +140,7 @@ impl<T> SharedQueue<T> {
 
         loop {
             if let Some(item) = state.queue.pop_front() {
+                self.not_full.notify_one();
                 return Some(item);
             }
 
@@ -88,35 +148,140 @@ impl<T> SharedQueue<T> {
                 return None;
             }
 
-            state = self.condvar.wait(state).unwrap();
+            state = self.not_empty.wait(state).unwrap();
         }
     }
 
+    // Non-blocking pop used by Selector::select to poll several queues in a row without
+    // parking on any single one of them.
+    fn try_dequeue(&self) -> Option<T> {
+        let mut state = self.state.lock().unwrap();
+        let item = state.queue.pop_front();
+        if item.is_some() {
+            self.not_full.notify_one();
+        }
+        item
+    }
+
+    fn is_done(&self) -> bool {
+        let state = self.state.lock().unwrap();
+        state.shutdown && state.queue.is_empty()
+    }
+
     fn send_shutdown(&self) {
         let mut state = self.state.lock().unwrap();
         state.shutdown = true;
-        self.condvar.notify_all();
+        self.not_empty.notify_all();
+        self.not_full.notify_all();
+        drop(state);
+        self.notify_wakeup();
     }
 
     fn size(&self) -> usize {
         let state = self.state.lock().unwrap();
         state.queue.len()
     }
+
+    // Registers the wakeup token a Selector wants notified whenever this queue gains an item
+    // or is shut down. Only one Selector may watch a given queue at a time.
+    fn register_wakeup(&self, token: Arc<WakeToken>) {
+        *self.wakeup.lock().unwrap() = Some(token);
+    }
+
+    fn notify_wakeup(&self) {
+        if let Some(token) = self.wakeup.lock().unwrap().as_ref() {
+            token.notify();
+        }
+    }
+}
+
+// Shared park point for Selector::select: a Condvar plus a ready-count that lets a waiter tell
+// "something happened since I last checked" apart from "it happened and then quieted back down
+// before I actually parked", the same generation trick used for the join barrier pattern.
+struct WakeToken {
+    ready: AtomicUsize,
+    mutex: Mutex<()>,
+    condvar: Condvar,
+}
+
+impl WakeToken {
+    fn new() -> Self {
+        WakeToken {
+            ready: AtomicUsize::new(0),
+            mutex: Mutex::new(()),
+            condvar: Condvar::new(),
+        }
+    }
+
+    fn notify(&self) {
+        self.ready.fetch_add(1, Ordering::SeqCst);
+        let _guard = self.mutex.lock().unwrap();
+        self.condvar.notify_all();
+    }
+
+    fn wait(&self, since: usize, timeout: Duration) {
+        let guard = self.mutex.lock().unwrap();
+        let _guard = self
+            .condvar
+            .wait_timeout_while(guard, timeout, |_| self.ready.load(Ordering::SeqCst) == since)
+            .unwrap();
+    }
+}
+
+// Waits across several SharedQueues at once, returning `(index, item)` for whichever registered
+// queue produces first. Consumers that only need one queue should keep using dequeue(); Selector
+// is for fanning a single consumer in across multiple producer pools.
+struct Selector<T> {
+    queues: Vec<Arc<SharedQueue<T>>>,
+    token: Arc<WakeToken>,
+}
+
+impl<T> Selector<T> {
+    fn new(queues: Vec<Arc<SharedQueue<T>>>) -> Self {
+        let token = Arc::new(WakeToken::new());
+        for queue in &queues {
+            queue.register_wakeup(Arc::clone(&token));
+        }
+        Selector { queues, token }
+    }
+
+    // Returns the first `(index, item)` to become available, or None once every registered
+    // queue has shut down and drained.
+    fn select(&self) -> Option<(usize, T)> {
+        loop {
+            for (index, queue) in self.queues.iter().enumerate() {
+                if let Some(item) = queue.try_dequeue() {
+                    return Some((index, item));
+                }
+            }
+
+            if self.queues.iter().all(|queue| queue.is_done()) {
+                return None;
+            }
+
+            let since = self.token.ready.load(Ordering::SeqCst);
+            self.token.wait(since, SELECT_POLL_INTERVAL);
+        }
+    }
 }
 
 fn main() {
     const TOTAL_ITEMS: usize = 1_000_000;
     const NUM_PRODUCERS: usize = 4;
     const NUM_CONSUMERS: usize = 4;
+    const QUEUE_CAPACITY: usize = 1_000;
 
     let items_per_producer = TOTAL_ITEMS / NUM_PRODUCERS;
-    let shared_queue = Arc::new(SharedQueue::<i32>::new());
+    let shared_queue = Arc::new(SharedQueue::<i32>::with_capacity(QUEUE_CAPACITY));
     let start_time = Instant::now();
     let mut consumer_handles = vec![];
     let mut producer_handles = vec![];
 
     println!("--- Starting Simulation ---");
-    println!("Producers: {}, Consumers: {}, Total Items: {}", NUM_PRODUCERS, NUM_CONSUMERS, TOTAL_ITEMS);
+    println!(
+        "Producers: {}, Consumers: {}, Total Items: {}, Queue Capacity: {}",
+        NUM_PRODUCERS, NUM_CONSUMERS, TOTAL_ITEMS, QUEUE_CAPACITY
+    );
 
     for id in 0..NUM_CONSUMERS {
         let q = Arc::clone(&shared_queue);
@@ -156,4 +321,51 @@ fn main() {
     println!("--- All operations complete ---");
     println!("Final Queue Size: {} (Should be 0)", shared_queue.size());
     println!("Time taken: {:.2?}", duration);
+
+    run_selector_demo();
+}
+
+// Demonstrates fanning a single consumer in across two producer pools via Selector, e.g. routing
+// both "/sleep" and fast requests through separate queues and serving whichever answers first.
+fn run_selector_demo() {
+    const ITEMS_PER_QUEUE: usize = 10_000;
+
+    println!("\n--- Starting Selector Fan-in Demo ---");
+
+    let sleep_queue = Arc::new(SharedQueue::<i32>::new());
+    let fast_queue = Arc::new(SharedQueue::<i32>::new());
+    let selector = Selector::new(vec![Arc::clone(&sleep_queue), Arc::clone(&fast_queue)]);
+
+    let producers = vec![
+        spawn_producer(Arc::clone(&sleep_queue), ITEMS_PER_QUEUE),
+        spawn_producer(Arc::clone(&fast_queue), ITEMS_PER_QUEUE),
+    ];
+
+    let mut received = vec![0usize; producers.len()];
+    while let Some((index, _item)) = selector.select() {
+        received[index] += 1;
+    }
+
+    for handle in producers {
+        handle.join().unwrap();
+    }
+
+    println!(
+        "Selector drained {} items from queue 0 (\"/sleep\") and {} from queue 1 (\"fast\").",
+        received[0], received[1]
+    );
+    assert_eq!(received[0], ITEMS_PER_QUEUE);
+    assert_eq!(received[1], ITEMS_PER_QUEUE);
+    println!("--- Selector demo complete ---");
+}
+
+// Spawns a producer that enqueues `count` items into `queue` and shuts it down once done, so
+// Selector::select can tell this queue apart from "temporarily empty".
+fn spawn_producer(queue: Arc<SharedQueue<i32>>, count: usize) -> thread::JoinHandle<()> {
+    thread::spawn(move || {
+        for i in 0..count {
+            queue.enqueue(i as i32);
+        }
+        queue.send_shutdown();
+    })
 }
\ No newline at end of file
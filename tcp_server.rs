@@ -1,47 +1,183 @@
+use std::cell::Cell;
+use std::collections::VecDeque;
 use std::io::{Read, Write};
 use std::net::{TcpListener, TcpStream};
-use std::sync::{mpsc, Arc, Mutex};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
 use std::thread;
 use std::time::Duration;
 
+thread_local! {
+    // Set for the lifetime of every worker thread. Lets `join` detect the barrier-deadlock
+    // footgun: a job that calls `ThreadPool::join` would block waiting on its own completion.
+    static ON_WORKER_THREAD: Cell<bool> = const { Cell::new(false) };
+}
+
 pub struct ThreadPool {
-    workers: Vec<Worker>,
-    sender: Option<mpsc::Sender<Job>>,
+    workers: Arc<Mutex<Vec<Worker>>>,
+    registry: Arc<Registry>,
 }
 
 type Job = Box<dyn FnOnce() + Send + 'static>;
 
+// How many jobs a worker pulls from the shared injector into its own deque at once, so the
+// injector lock is paid for once per batch instead of once per job.
+const STEAL_BATCH: usize = 32;
+
+// How long an idle worker naps between steal attempts. Bounds wake-up latency without needing
+// every job hand-off to prove it notified the right sleeper.
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+// Shared scheduling state for the pool: one global injector that `execute` feeds, and one
+// mutex-guarded deque per worker that the owning worker pushes/pops from the bottom while
+// siblings steal from the top. This is a sharded-mutex approximation of a Chase-Lev deque, not
+// a lock-free one — every push/pop still takes that worker's own lock, it's just a separate lock
+// per worker instead of one lock shared by all `size` workers, so most `pop`s only ever contend
+// with a steal in progress rather than with every other worker's own pushes.
+struct Registry {
+    injector: Mutex<VecDeque<Job>>,
+    deques: Vec<Mutex<VecDeque<Job>>>,
+    parked: Mutex<()>,
+    cond: Condvar,
+    shutdown: AtomicBool,
+    live_workers: AtomicUsize,
+    panic_count: AtomicUsize,
+    barrier: Barrier,
+}
+
+impl Registry {
+    fn new(size: usize) -> Self {
+        Registry {
+            injector: Mutex::new(VecDeque::new()),
+            deques: (0..size).map(|_| Mutex::new(VecDeque::new())).collect(),
+            parked: Mutex::new(()),
+            cond: Condvar::new(),
+            shutdown: AtomicBool::new(false),
+            live_workers: AtomicUsize::new(0),
+            panic_count: AtomicUsize::new(0),
+            barrier: Barrier::new(),
+        }
+    }
+
+    fn wake_all(&self) {
+        self.cond.notify_all();
+    }
+}
+
+// Lets `ThreadPool::join` block until every job queued before the call has finished, without
+// shutting the pool down. `outstanding` counts jobs that have been queued but not yet finished;
+// `generation` is bumped each time `outstanding` touches zero, so a waiter that re-checks after
+// being notified can tell "this is the zero-crossing I was waiting for" apart from "it hit zero
+// and immediately got busy again before I woke up".
+struct Barrier {
+    outstanding: AtomicUsize,
+    generation: AtomicUsize,
+    lock: Mutex<()>,
+    cond: Condvar,
+}
+
+impl Barrier {
+    fn new() -> Self {
+        Barrier {
+            outstanding: AtomicUsize::new(0),
+            generation: AtomicUsize::new(0),
+            lock: Mutex::new(()),
+            cond: Condvar::new(),
+        }
+    }
+
+    fn job_queued(&self) {
+        self.outstanding.fetch_add(1, Ordering::SeqCst);
+    }
+
+    fn job_finished(&self) {
+        if self.outstanding.fetch_sub(1, Ordering::SeqCst) == 1 {
+            let _guard = self.lock.lock().unwrap();
+            self.generation.fetch_add(1, Ordering::SeqCst);
+            self.cond.notify_all();
+        }
+    }
+
+    fn join(&self) {
+        if self.outstanding.load(Ordering::SeqCst) == 0 {
+            return;
+        }
+
+        let start_generation = self.generation.load(Ordering::SeqCst);
+        let guard = self.lock.lock().unwrap();
+        let _guard = self
+            .cond
+            .wait_while(guard, |_| {
+                self.outstanding.load(Ordering::SeqCst) > 0
+                    && self.generation.load(Ordering::SeqCst) == start_generation
+            })
+            .unwrap();
+    }
+}
+
 impl ThreadPool {
     pub fn new(size: usize) -> ThreadPool {
-        
-        let (sender, receiver) = mpsc::channel();
-        let receiver = Arc::new(Mutex::new(receiver));
-        let mut workers = Vec::with_capacity(size);
+        let registry = Arc::new(Registry::new(size));
+        let workers = Arc::new(Mutex::new(Vec::with_capacity(size)));
 
-        for id in 0..size {
-            workers.push(Worker::new(id, Arc::clone(&receiver)));
+        {
+            let mut guard = workers.lock().unwrap();
+            for id in 0..size {
+                let thread = Worker::spawn(id, Arc::clone(&registry), Arc::clone(&workers));
+                guard.push(Worker {
+                    id,
+                    thread: Some(thread),
+                });
+            }
         }
 
-        ThreadPool {
-            workers,
-            sender: Some(sender),
-        }
+        ThreadPool { workers, registry }
     }
 
     pub fn execute<F>(&self, f: F)
     where
         F: FnOnce() + Send + 'static,
     {
-        let job = Box::new(f);
-        self.sender.as_ref().unwrap().send(job).unwrap();
+        let job: Job = Box::new(f);
+        self.registry.barrier.job_queued();
+        self.registry.injector.lock().unwrap().push_back(job);
+        self.registry.wake_all();
+    }
+
+    /// Number of jobs that panicked so far. Panics are caught and logged, not propagated, so
+    /// this is how a caller observes that handlers are dying without the server itself going down.
+    pub fn panic_count(&self) -> usize {
+        self.registry.panic_count.load(Ordering::SeqCst)
+    }
+
+    /// Number of workers currently alive. Transiently dips below the configured `size` from the
+    /// moment `Sentinel::drop` of a panicked worker runs until the replacement it spawns has
+    /// started (and reads below `size` right after `new`, until the workers have started);
+    /// callers that want to confirm the pool has recovered should poll this back up to `size`.
+    pub fn live_workers(&self) -> usize {
+        self.registry.live_workers.load(Ordering::SeqCst)
+    }
+
+    /// Blocks until every job submitted before this call has finished. The pool keeps running
+    /// afterwards and can be reused. Panics if called from any `ThreadPool` worker thread (the
+    /// check does not tell pools apart); on this pool's own workers the calling job could never
+    /// finish while it's blocked waiting on itself.
+    pub fn join(&self) {
+        if ON_WORKER_THREAD.with(|on_worker| on_worker.get()) {
+            panic!("ThreadPool::join called from a worker thread; this would deadlock");
+        }
+        self.registry.barrier.join();
     }
 }
 
 impl Drop for ThreadPool {
     fn drop(&mut self) {
-        drop(self.sender.take());
+        self.registry.shutdown.store(true, Ordering::SeqCst);
+        self.registry.wake_all();
 
-        for worker in &mut self.workers {
+        let mut workers = self.workers.lock().unwrap();
+        for worker in workers.iter_mut() {
             println!("Shutting down worker {}", worker.id);
 
             if let Some(thread) = worker.thread.take() {
@@ -56,27 +192,128 @@ struct Worker {
     thread: Option<thread::JoinHandle<()>>,
 }
 
+// Detects a worker thread unwinding out of its run loop entirely (as opposed to a job panic,
+// which is caught and swallowed inside the loop) and respawns a replacement so the pool never
+// silently shrinks below its configured size, mirroring the `threadpool` crate's sentinel.
+struct Sentinel {
+    id: usize,
+    registry: Arc<Registry>,
+    workers: Arc<Mutex<Vec<Worker>>>,
+    active: bool,
+}
+
+impl Sentinel {
+    fn new(id: usize, registry: Arc<Registry>, workers: Arc<Mutex<Vec<Worker>>>) -> Sentinel {
+        Sentinel {
+            id,
+            registry,
+            workers,
+            active: true,
+        }
+    }
+
+    fn cancel(mut self) {
+        self.active = false;
+    }
+}
+
+impl Drop for Sentinel {
+    fn drop(&mut self) {
+        self.registry.live_workers.fetch_sub(1, Ordering::SeqCst);
+
+        if self.active && thread::panicking() && !self.registry.shutdown.load(Ordering::SeqCst) {
+            eprintln!("Worker {} terminated unexpectedly; respawning.", self.id);
+            let thread = Worker::spawn(self.id, Arc::clone(&self.registry), Arc::clone(&self.workers));
+            let mut workers = self.workers.lock().unwrap();
+            if let Some(slot) = workers.iter_mut().find(|w| w.id == self.id) {
+                slot.thread = Some(thread);
+            }
+        }
+    }
+}
+
 impl Worker {
-    fn new(id: usize, receiver: Arc<Mutex<mpsc::Receiver<Job>>>) -> Worker {
-        let thread = thread::spawn(move || loop {
-            let message = receiver.lock().unwrap().recv();
+    fn spawn(id: usize, registry: Arc<Registry>, workers: Arc<Mutex<Vec<Worker>>>) -> thread::JoinHandle<()> {
+        thread::spawn(move || {
+            registry.live_workers.fetch_add(1, Ordering::SeqCst);
+            ON_WORKER_THREAD.with(|on_worker| on_worker.set(true));
+            let sentinel = Sentinel::new(id, Arc::clone(&registry), workers);
 
-            match message {
-                Ok(job) => {
+            // Seed the per-worker steal RNG from the worker id so each thread picks a different
+            // victim order; doesn't need to be cryptographically random, just spread out.
+            let mut rng_state = 0x9E3779B97F4A7C15u64 ^ ((id as u64) + 1);
+
+            loop {
+                if let Some(job) = Worker::find_job(&registry, id, &mut rng_state) {
                     println!("Worker {id} got a job; executing.");
-                    job();
+                    if panic::catch_unwind(AssertUnwindSafe(job)).is_err() {
+                        registry.panic_count.fetch_add(1, Ordering::SeqCst);
+                        eprintln!("Worker {id}: job panicked; recovering.");
+                    }
+                    registry.barrier.job_finished();
+                    continue;
                 }
-                Err(_) => {
-                    println!("Worker {id} disconnected; shutting down.");
+
+                if registry.shutdown.load(Ordering::SeqCst) {
                     break;
                 }
+
+                let guard = registry.parked.lock().unwrap();
+                let _ = registry.cond.wait_timeout(guard, POLL_INTERVAL).unwrap();
             }
-        });
 
-        Worker {
-            id,
-            thread: Some(thread),
+            println!("Worker {id} disconnected; shutting down.");
+            sentinel.cancel();
+        })
+    }
+
+    // Pop order: our own deque's bottom, then a batch pulled in from the global injector, then a
+    // steal attempt against sibling deques' tops, swept in order starting from a random sibling.
+    fn find_job(registry: &Registry, id: usize, rng_state: &mut u64) -> Option<Job> {
+        if let Some(job) = registry.deques[id].lock().unwrap().pop_back() {
+            return Some(job);
+        }
+
+        {
+            let mut injector = registry.injector.lock().unwrap();
+            if !injector.is_empty() {
+                let mut own = registry.deques[id].lock().unwrap();
+                for _ in 0..STEAL_BATCH {
+                    match injector.pop_front() {
+                        Some(job) => own.push_back(job),
+                        None => break,
+                    }
+                }
+            }
+        }
+
+        if let Some(job) = registry.deques[id].lock().unwrap().pop_back() {
+            return Some(job);
         }
+
+        let worker_count = registry.deques.len();
+        if worker_count > 1 {
+            let start = (Worker::next_rand(rng_state) as usize) % worker_count;
+            for offset in 0..worker_count {
+                let victim = (start + offset) % worker_count;
+                if victim == id {
+                    continue;
+                }
+                if let Some(job) = registry.deques[victim].lock().unwrap().pop_front() {
+                    return Some(job);
+                }
+            }
+        }
+
+        None
+    }
+
+    // xorshift64: plenty uniform for picking a steal victim, no external crate needed.
+    fn next_rand(state: &mut u64) -> u64 {
+        *state ^= *state << 13;
+        *state ^= *state >> 7;
+        *state ^= *state << 17;
+        *state
     }
 }
 
@@ -140,4 +377,4 @@ fn handle_connection(mut stream: TcpStream) {
             eprintln!("Failed to read from stream: {}", e);
         }
     }
-}
\ No newline at end of file
+}